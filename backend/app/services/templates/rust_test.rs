@@ -1,15 +1,61 @@
-// Suppress warnings for generated test code
-#![allow(dead_code)]
-#![allow(unused_variables)]
-#![allow(unused_imports)]
-#![allow(unused_macros)]
+// Suppress warnings for generated test code, unless strict mode is on.
+// In strict mode the lint-suppression block below is emitted empty so the
+// compiler's warnings (unused_variables, dead_code, etc.) aren't swallowed
+// and can be captured from the build step's stderr instead.
+$lint_allows
 
-use std::io::{self, Write};
 use std::panic;
+use std::sync::Mutex;
+
+// Collects the output written through `cap_print`/`cap_println` while the
+// current test runs. `_stubs` can define print shims (e.g. a
+// `print_line(s: &str)`) that write here instead of going to the real
+// stdout, so each TestResult carries the output produced through those
+// shims. Plain `print!`/`println!` calls still go to the real stdout and
+// are not captured.
+static CAPTURE: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+fn capture_clear() {
+    CAPTURE.lock().unwrap().clear();
+}
+
+fn capture_take() -> String {
+    let mut buf = CAPTURE.lock().unwrap();
+    let captured = String::from_utf8_lossy(&buf).into_owned();
+    buf.clear();
+    captured
+}
+
+#[allow(dead_code)]
+fn cap_print(s: &str) {
+    CAPTURE.lock().unwrap().extend_from_slice(s.as_bytes());
+}
+
+#[allow(dead_code)]
+fn cap_println(s: &str) {
+    let mut buf = CAPTURE.lock().unwrap();
+    buf.extend_from_slice(s.as_bytes());
+    buf.push(b'\n');
+}
+
+// Escapes a captured output payload onto a single physical line so a
+// line-oriented parser can find the whole `OUTPUT_{id}:` block without
+// the rest of a multi-line capture leaking out as unattributed lines.
+fn escape_marker_payload(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
 
 // Default stub module - provides fallback implementations
-// Student code can override these by defining functions with the same name
+// Student code can override these by defining functions with the same name.
+// Exempted from dead_code/unused lints even in strict mode: a stub that a
+// student overrides (or never calls a capture shim from) is harness
+// plumbing, not a submission mistake, and shouldn't show up as a warning.
+#[allow(dead_code, unused_variables, unused_imports, unused_macros)]
 mod _stubs {
+    // Lets stub bodies call the unqualified `cap_print`/`cap_println` shims
+    // to write into the capture buffer.
+    use super::{cap_print, cap_println};
+
 $stub_functions
 }
 
@@ -25,44 +71,165 @@ struct TestResult {
     id: i32,
     passed: bool,
     points: i32,
+    // Failure is reported but never counts against the student (optional
+    // or known-flaky cases): a failing run is left out of `total`, while a
+    // passing run counts toward `earned` and `total` like any other test.
+    allow_fail: bool,
+    // Bonus test: a pass adds to `earned` without inflating `total`.
+    extra_credit: bool,
     error_msg: Option<String>,
     output: Option<String>,
 }
 
-fn main() {
-    // Note: Rust doesn't easily support stdout capture at runtime like other languages
-    // Console output from student code will appear in the normal output
-    // We print a marker so the parser knows where console output might be
-    
-    let mut test_results: Vec<TestResult> = Vec::new();
-    
+// Each test is registered as an (id, closure) pair rather than executed
+// directly, so a single test can be run in isolation: the grader driver
+// spawns this binary once per test id via `--run-test <id>`, enforcing a
+// wall-clock timeout and killing the child on expiry. A child that never
+// reports back is recorded by the driver as a TestResult with
+// passed=false and error_msg="timeout". Running with no arguments falls
+// back to executing every registered test in-process, for callers that
+// don't need isolation.
+fn test_registry() -> Vec<(i32, Box<dyn Fn() -> TestResult>)> {
+    let mut registry: Vec<(i32, Box<dyn Fn() -> TestResult>)> = Vec::new();
+
     // Test execution
     $test_execution_code
-    
-    // Summary output
+
+    registry
+}
+
+// Extracts a human-readable message from a caught panic payload.
+#[allow(dead_code)]
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+// Runs a `should_panic`-style test closure, used by the generated test
+// execution block for assignments where the student's code is expected
+// to panic. Passes
+// only if a panic occurred and, when `expected` is given, the panic
+// message contains that substring; the observed panic message (or lack
+// thereof) is always returned so mismatches are reported clearly.
+//
+// The default panic hook is swapped out only for the duration of
+// `catch_unwind` so the default `thread panicked at ...` message still
+// prints for genuine, unexpected panics in ordinary tests.
+#[allow(dead_code)]
+fn run_should_panic(
+    expected: Option<&str>,
+    f: impl FnOnce() + panic::UnwindSafe,
+) -> (bool, Option<String>) {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(f);
+    panic::set_hook(previous_hook);
+
+    match result {
+        Ok(()) => (false, Some("expected panic, code returned normally".to_string())),
+        Err(payload) => {
+            let msg = panic_message(payload.as_ref());
+            match expected {
+                Some(expected) if !msg.contains(expected) => (
+                    false,
+                    Some(format!("expected panic containing \"{}\", got \"{}\"", expected, msg)),
+                ),
+                _ => (true, Some(msg)),
+            }
+        }
+    }
+}
+
+// Runs a single registered test, capturing any output it wrote through
+// the `cap_print`/`cap_println` shims and attaching it to the result.
+fn run_test(run: &dyn Fn() -> TestResult) -> TestResult {
+    capture_clear();
+    let mut result = run();
+    let captured = capture_take();
+    if result.output.is_none() && !captured.is_empty() {
+        result.output = Some(captured);
+    }
+    result
+}
+
+fn print_summary(test_results: &[TestResult]) {
     let mut passed = 0;
     let mut failed = 0;
+    let mut allowed_failures = 0;
     let mut earned = 0;
     let mut total = 0;
-    
-    for r in &test_results {
+
+    for r in test_results {
+        if let Some(ref out) = r.output {
+            println!("OUTPUT_{}: {}", r.id, escape_marker_payload(out));
+        }
         if r.passed {
             passed += 1;
             earned += r.points;
+            if !r.extra_credit {
+                total += r.points;
+            }
+        } else if r.allow_fail {
+            allowed_failures += 1;
+            // Reported for visibility but kept out of `total` so it can't
+            // count against the student.
+            if let Some(ref err) = r.error_msg {
+                println!("ALLOWFAIL_{}: {}", r.id, err);
+            }
         } else {
             failed += 1;
             // Print error info for failed tests
             if let Some(ref err) = r.error_msg {
                 println!("ERROR_{}: {}", r.id, err);
             }
+            if !r.extra_credit {
+                total += r.points;
+            }
         }
-        total += r.points;
     }
-    
+
     println!("\n=== Test Results ===");
     println!("Passed: {}", passed);
     println!("Failed: {}", failed);
+    println!("AllowedFailures: {}", allowed_failures);
     println!("Total: {}", test_results.len());
     println!("Earned: {}", earned);
     println!("TotalPoints: {}", total);
 }
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let registry = test_registry();
+
+    if let Some(pos) = args.iter().position(|a| a == "--run-test") {
+        let id: i32 = args
+            .get(pos + 1)
+            .and_then(|s| s.parse().ok())
+            .expect("--run-test requires a numeric test id");
+
+        let result = registry
+            .iter()
+            .find(|(test_id, _)| *test_id == id)
+            .map(|(_, run)| run_test(run.as_ref()))
+            .unwrap_or_else(|| TestResult {
+                id,
+                passed: false,
+                points: 0,
+                allow_fail: false,
+                extra_credit: false,
+                error_msg: Some(format!("no such test id: {}", id)),
+                output: None,
+            });
+
+        print_summary(&[result]);
+        return;
+    }
+
+    let test_results: Vec<TestResult> = registry.iter().map(|(_, run)| run_test(run.as_ref())).collect();
+    print_summary(&test_results);
+}